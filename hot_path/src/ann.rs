@@ -0,0 +1,273 @@
+//! Approximate nearest-neighbor search over embedding vectors.
+//!
+//! A minimal HNSW (Hierarchical Navigable Small World) index, built
+//! incrementally as vectors are inserted so it can grow alongside
+//! `HierarchicalClassifier::load_patterns_from_file`/`load_level_schemas`.
+//! Exact brute-force search remains available as a fallback mode via
+//! [`HnswIndex::brute_force_search`] when callers need guaranteed recall.
+
+use rand::Rng;
+use std::collections::{HashMap, HashSet};
+
+const DEFAULT_M: usize = 16;
+const DEFAULT_M_MAX0: usize = 32;
+const DEFAULT_EF_CONSTRUCTION: usize = 100;
+
+#[derive(Debug, Clone)]
+struct HnswNode {
+    id: String,
+    vector: Vec<f32>,
+    /// Neighbor node indices, one list per layer the node participates in.
+    neighbors: Vec<Vec<usize>>,
+}
+
+/// Incrementally-built HNSW index over `(id, vector)` pairs.
+pub struct HnswIndex {
+    nodes: Vec<HnswNode>,
+    id_to_index: HashMap<String, usize>,
+    entry_point: Option<usize>,
+    m: usize,
+    m_max0: usize,
+    ef_construction: usize,
+    /// Level-generation multiplier `mL`, conventionally `1 / ln(M)`.
+    level_multiplier: f64,
+}
+
+impl HnswIndex {
+    pub fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            id_to_index: HashMap::new(),
+            entry_point: None,
+            m: DEFAULT_M,
+            m_max0: DEFAULT_M_MAX0,
+            ef_construction: DEFAULT_EF_CONSTRUCTION,
+            level_multiplier: 1.0 / (DEFAULT_M as f64).ln(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    fn sample_level(&self) -> usize {
+        let uniform: f64 = rand::thread_rng().gen_range(f64::EPSILON..1.0);
+        (-uniform.ln() * self.level_multiplier).floor() as usize
+    }
+
+    /// Insert a vector, replacing any existing node with the same id.
+    pub fn insert(&mut self, id: String, vector: Vec<f32>) {
+        if let Some(&existing) = self.id_to_index.get(&id) {
+            self.nodes[existing].vector = vector;
+            return;
+        }
+
+        let level = self.sample_level();
+        let new_idx = self.nodes.len();
+        self.nodes.push(HnswNode { id: id.clone(), vector: vector.clone(), neighbors: vec![Vec::new(); level + 1] });
+        self.id_to_index.insert(id, new_idx);
+
+        let entry_point = match self.entry_point {
+            None => {
+                self.entry_point = Some(new_idx);
+                return;
+            }
+            Some(ep) => ep,
+        };
+
+        let top_level = self.nodes[entry_point].neighbors.len() - 1;
+        let mut cur = entry_point;
+
+        // Descend greedily from the top layer down to one above our insertion level.
+        for lc in (level.min(top_level) + 1..=top_level).rev() {
+            cur = self.greedy_closest(cur, &vector, lc);
+        }
+
+        // At and below our insertion level, find candidates and connect bidirectionally.
+        for lc in (0..=level.min(top_level)).rev() {
+            let candidates = self.search_layer(cur, &vector, self.ef_construction, lc);
+            let max_conn = if lc == 0 { self.m_max0 } else { self.m };
+            let selected = self.select_neighbors(&candidates, max_conn);
+
+            for &neighbor_idx in &selected {
+                self.nodes[new_idx].neighbors[lc].push(neighbor_idx);
+                if lc < self.nodes[neighbor_idx].neighbors.len() {
+                    self.nodes[neighbor_idx].neighbors[lc].push(new_idx);
+                    self.prune_neighbors(neighbor_idx, lc, max_conn);
+                }
+            }
+            if let Some(&(closest, _)) = candidates.first() {
+                cur = closest;
+            }
+        }
+
+        if level > top_level {
+            self.entry_point = Some(new_idx);
+        }
+    }
+
+    fn prune_neighbors(&mut self, node_idx: usize, layer: usize, max_conn: usize) {
+        if self.nodes[node_idx].neighbors[layer].len() <= max_conn {
+            return;
+        }
+        let vector = self.nodes[node_idx].vector.clone();
+        let mut scored: Vec<(usize, f32)> = self.nodes[node_idx].neighbors[layer].iter()
+            .map(|&n| (n, cosine_similarity(&vector, &self.nodes[n].vector)))
+            .collect();
+        scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        scored.truncate(max_conn);
+        self.nodes[node_idx].neighbors[layer] = scored.into_iter().map(|(n, _)| n).collect();
+    }
+
+    fn greedy_closest(&self, from: usize, query: &[f32], layer: usize) -> usize {
+        let mut best = from;
+        let mut best_sim = cosine_similarity(query, &self.nodes[from].vector);
+        loop {
+            let mut improved = false;
+            if layer >= self.nodes[best].neighbors.len() {
+                break;
+            }
+            for &neighbor in &self.nodes[best].neighbors[layer] {
+                let sim = cosine_similarity(query, &self.nodes[neighbor].vector);
+                if sim > best_sim {
+                    best_sim = sim;
+                    best = neighbor;
+                    improved = true;
+                }
+            }
+            if !improved {
+                break;
+            }
+        }
+        best
+    }
+
+    /// `ef`-bounded best-first search at a single layer, returning candidates
+    /// sorted by descending similarity.
+    fn search_layer(&self, entry: usize, query: &[f32], ef: usize, layer: usize) -> Vec<(usize, f32)> {
+        let mut visited: HashSet<usize> = HashSet::new();
+        visited.insert(entry);
+
+        let entry_sim = cosine_similarity(query, &self.nodes[entry].vector);
+        let mut candidates = vec![(entry, entry_sim)];
+        let mut results = vec![(entry, entry_sim)];
+
+        while let Some(&(current, current_sim)) = candidates.last() {
+            candidates.pop();
+            let worst_in_results = results.iter().map(|&(_, s)| s).fold(f32::INFINITY, f32::min);
+            if current_sim < worst_in_results && results.len() >= ef {
+                break;
+            }
+
+            if layer >= self.nodes[current].neighbors.len() {
+                continue;
+            }
+            for &neighbor in &self.nodes[current].neighbors[layer] {
+                if !visited.insert(neighbor) {
+                    continue;
+                }
+                let sim = cosine_similarity(query, &self.nodes[neighbor].vector);
+                results.push((neighbor, sim));
+                candidates.push((neighbor, sim));
+                candidates.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+            }
+
+            results.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+            results.truncate(ef.max(1));
+        }
+
+        results.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        results
+    }
+
+    fn select_neighbors(&self, candidates: &[(usize, f32)], m: usize) -> Vec<usize> {
+        candidates.iter().take(m).map(|&(idx, _)| idx).collect()
+    }
+
+    /// Approximate top-k search. Explores `max(ef_search, k)` candidates at
+    /// layer 0 and returns the best `k`; callers that post-filter (e.g. a
+    /// subspace-prefix filter) over-fetch by passing a larger `k`.
+    pub fn search(&self, query: &[f32], k: usize, ef_search: usize) -> Vec<(String, f32)> {
+        let Some(entry_point) = self.entry_point else { return Vec::new(); };
+
+        let top_level = self.nodes[entry_point].neighbors.len() - 1;
+        let mut cur = entry_point;
+        for lc in (1..=top_level).rev() {
+            cur = self.greedy_closest(cur, query, lc);
+        }
+
+        let ef = ef_search.max(k);
+        let mut results = self.search_layer(cur, query, ef, 0);
+        results.truncate(k);
+        results.into_iter().map(|(idx, sim)| (self.nodes[idx].id.clone(), sim)).collect()
+    }
+
+    /// Exact brute-force search, used as the exactness fallback mode.
+    pub fn brute_force_search(&self, query: &[f32], k: usize) -> Vec<(String, f32)> {
+        let mut scored: Vec<(String, f32)> = self.nodes.iter()
+            .map(|n| (n.id.clone(), cosine_similarity(query, &n.vector)))
+            .collect();
+        scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        scored.truncate(k);
+        scored
+    }
+}
+
+impl Default for HnswIndex {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() { return 0.0; }
+    let dot_product: f32 = a.iter().zip(b.iter()).map(|(x, y)| x * y).sum();
+    let norm_a: f32 = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b: f32 = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 { 0.0 } else { dot_product / (norm_a * norm_b) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn random_vector(dim: usize, rng: &mut impl Rng) -> Vec<f32> {
+        (0..dim).map(|_| rng.gen_range(-1.0..1.0)).collect()
+    }
+
+    #[test]
+    fn search_returns_at_most_k_results() {
+        let mut rng = rand::thread_rng();
+        let mut index = HnswIndex::new();
+        for i in 0..50 {
+            index.insert(format!("vec-{}", i), random_vector(16, &mut rng));
+        }
+
+        let query = random_vector(16, &mut rng);
+        let results = index.search(&query, 5, 50);
+        assert_eq!(results.len(), 5, "search should truncate to k, not ef_search");
+    }
+
+    #[test]
+    fn search_matches_brute_force_recall() {
+        let mut rng = rand::thread_rng();
+        let mut index = HnswIndex::new();
+        for i in 0..200 {
+            index.insert(format!("vec-{}", i), random_vector(16, &mut rng));
+        }
+
+        let query = random_vector(16, &mut rng);
+        let k = 10;
+        let approx: HashSet<String> = index.search(&query, k, 200).into_iter().map(|(id, _)| id).collect();
+        let exact: HashSet<String> = index.brute_force_search(&query, k).into_iter().map(|(id, _)| id).collect();
+
+        // A generous ef_search over a small index should recall most of the
+        // true top-k; this isn't exact search, so allow some slack.
+        let recall = approx.intersection(&exact).count() as f64 / k as f64;
+        assert!(recall >= 0.7, "approximate search recall too low: {:.2}", recall);
+    }
+}
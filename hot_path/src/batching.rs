@@ -0,0 +1,158 @@
+//! Micro-batches concurrent `/classify` requests into a single embedding
+//! pass on the hot path.
+//!
+//! A background task owns the buffer: each request is pushed on with its
+//! own `oneshot::Sender` and awaits the result; the task flushes - running
+//! one batched embedding pass followed by a per-item classification -
+//! whenever the buffer reaches `batch_size` or `max_batch_latency_ms`
+//! elapses since the first item was buffered, whichever comes first. This
+//! trades a few milliseconds of latency for much higher embedding
+//! throughput under concurrent load.
+
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot, watch};
+use tokio::time::Instant;
+use tracing::{info, warn};
+
+use crate::classifier::{ClassifyParams, HierarchicalClassifier};
+use crate::config::Config;
+use crate::errors::{ClassificationStage, ErrorReporter};
+use crate::generated::{PatternMatch, WeaveUnit};
+
+pub type ClassifyOutcome = anyhow::Result<(Option<PatternMatch>, Vec<PatternMatch>, Vec<String>)>;
+
+/// One buffered request. `params` is kept per-item rather than hoisted to
+/// the batch, since concurrent callers aren't guaranteed to share the same
+/// classification knobs.
+struct BufferedRequest {
+    request_id: String,
+    weave_unit: WeaveUnit,
+    params: ClassifyParams,
+    responder: oneshot::Sender<ClassifyOutcome>,
+}
+
+/// Handle for submitting requests to the coalescing buffer; the
+/// buffer/flush loop runs on a background task so callers just await their
+/// own `oneshot`. Cheaply `Clone`-able - clones share the same task.
+#[derive(Clone)]
+pub struct EmbeddingBatcher {
+    sender: mpsc::UnboundedSender<BufferedRequest>,
+    stop_tx: watch::Sender<bool>,
+}
+
+impl EmbeddingBatcher {
+    /// Spawn the background coalescing task. `batch_size`/`max_batch_latency_ms`
+    /// are re-read from `config` at the start of each buffering round (rather
+    /// than captured once), so `/reload-config` can retune them without a
+    /// restart; `batch_size` doubles as the buffer's memory cap in the
+    /// meantime - a full buffer flushes immediately rather than growing further.
+    pub fn new(
+        classifier: Arc<HierarchicalClassifier>,
+        error_reporter: Arc<ErrorReporter>,
+        config: Arc<RwLock<Config>>,
+    ) -> Self {
+        let (sender, mut receiver) = mpsc::unbounded_channel::<BufferedRequest>();
+        let (stop_tx, mut stop_rx) = watch::channel(false);
+
+        tokio::spawn(async move {
+            info!("📦 Embedding batcher started");
+            let mut buffer: Vec<BufferedRequest> = Vec::new();
+
+            loop {
+                if buffer.is_empty() {
+                    tokio::select! {
+                        item = receiver.recv() => {
+                            match item {
+                                Some(item) => buffer.push(item),
+                                None => break,
+                            }
+                        }
+                        _ = stop_rx.changed() => break,
+                    }
+                }
+
+                let (batch_size, max_batch_latency_ms) = config.read()
+                    .map(|c| (c.embed_batch_size, c.embed_max_batch_latency_ms))
+                    .unwrap_or_else(|e| { let c = e.into_inner(); (c.embed_batch_size, c.embed_max_batch_latency_ms) });
+                let batch_size = batch_size.max(1);
+                let max_batch_latency = Duration::from_millis(max_batch_latency_ms.max(1));
+
+                let deadline = Instant::now() + max_batch_latency;
+                while buffer.len() < batch_size {
+                    tokio::select! {
+                        item = receiver.recv() => {
+                            match item {
+                                Some(item) => buffer.push(item),
+                                None => break,
+                            }
+                        }
+                        _ = tokio::time::sleep_until(deadline) => break,
+                    }
+                }
+
+                flush(&classifier, &error_reporter, std::mem::take(&mut buffer)).await;
+            }
+
+            // Drain and flush whatever is left so no caller hangs on shutdown.
+            let mut remaining = Vec::new();
+            while let Ok(item) = receiver.try_recv() {
+                remaining.push(item);
+            }
+            flush(&classifier, &error_reporter, remaining).await;
+
+            info!("📦 Embedding batcher stopped");
+        });
+
+        Self { sender, stop_tx }
+    }
+
+    /// Submit a request and await its classification result once the batch
+    /// it lands in is flushed.
+    pub async fn submit(&self, request_id: String, weave_unit: WeaveUnit, params: ClassifyParams) -> ClassifyOutcome {
+        let (responder, receiver) = oneshot::channel();
+        let request = BufferedRequest { request_id, weave_unit, params, responder };
+
+        if self.sender.send(request).is_err() {
+            return Err(anyhow::anyhow!("Embedding batcher is shut down"));
+        }
+
+        receiver.await.map_err(|_| anyhow::anyhow!("Embedding batcher dropped the request before responding"))?
+    }
+
+    /// Stop accepting new batches once the in-flight buffer drains; wired
+    /// into the server's graceful shutdown so no request is left hanging.
+    pub fn shutdown(&self) {
+        let _ = self.stop_tx.send(true);
+    }
+}
+
+async fn flush(classifier: &HierarchicalClassifier, error_reporter: &ErrorReporter, batch: Vec<BufferedRequest>) {
+    if batch.is_empty() {
+        return;
+    }
+
+    let texts: Vec<&str> = batch.iter().map(|r| r.weave_unit.text.as_str()).collect();
+    let embeddings = match classifier.encode_batch(&texts).await {
+        Ok(embeddings) => embeddings,
+        Err(e) => {
+            warn!("Batched embedding pass failed for {} request(s): {}", batch.len(), e);
+            for request in batch {
+                let message = e.to_string();
+                error_reporter.report(ClassificationStage::Embedding, &request.request_id, request.weave_unit.text.len(), &message);
+                let _ = request.responder.send(Err(anyhow::anyhow!("Batched embedding pass failed: {}", message)));
+            }
+            return;
+        }
+    };
+
+    for (request, embedding) in batch.into_iter().zip(embeddings.into_iter()) {
+        let outcome = classifier.classify_with_embedding(embedding, &request.weave_unit.text, &request.params).await;
+
+        if let Err(e) = &outcome {
+            error_reporter.report(ClassificationStage::Search, &request.request_id, request.weave_unit.text.len(), &e.to_string());
+        }
+
+        let _ = request.responder.send(outcome);
+    }
+}
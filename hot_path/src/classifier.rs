@@ -4,6 +4,7 @@ use anyhow::{anyhow, Result};
 use tracing::{info, warn, debug};
 use serde_json::Value;
 
+use crate::ann::HnswIndex;
 use crate::embeddings::EmbeddingGenerator;
 use crate::config::Config;
 use crate::generated::PatternMatch;
@@ -14,6 +15,105 @@ struct VectorPoint {
     pub payload: HashMap<String, Value>,
 }
 
+const BM25_K1: f64 = 1.2;
+const BM25_B: f64 = 0.75;
+
+/// Inverted index over pattern `description`/`pattern_id` text, used to compute
+/// BM25 lexical scores alongside the vector similarity search.
+#[derive(Debug, Clone, Default)]
+struct Bm25Index {
+    /// term -> (pattern_id, term frequency in that pattern's document)
+    postings: HashMap<String, Vec<(String, usize)>>,
+    doc_lengths: HashMap<String, usize>,
+    avgdl: f64,
+    doc_count: usize,
+}
+
+impl Bm25Index {
+    fn build(documents: &HashMap<String, String>) -> Self {
+        let mut postings: HashMap<String, Vec<(String, usize)>> = HashMap::new();
+        let mut doc_lengths = HashMap::new();
+        let mut total_len = 0usize;
+
+        for (pattern_id, text) in documents {
+            let tokens = tokenize(text);
+            total_len += tokens.len();
+            doc_lengths.insert(pattern_id.clone(), tokens.len());
+
+            let mut term_freqs: HashMap<String, usize> = HashMap::new();
+            for token in tokens {
+                *term_freqs.entry(token).or_insert(0) += 1;
+            }
+            for (term, freq) in term_freqs {
+                postings.entry(term).or_default().push((pattern_id.clone(), freq));
+            }
+        }
+
+        let doc_count = documents.len();
+        let avgdl = if doc_count > 0 { total_len as f64 / doc_count as f64 } else { 0.0 };
+
+        Self { postings, doc_lengths, avgdl, doc_count }
+    }
+
+    /// Score a single pattern against the query tokens. Patterns with no
+    /// lexical overlap simply score 0.
+    fn score(&self, query_tokens: &[String], pattern_id: &str) -> f64 {
+        if self.doc_count == 0 || self.avgdl == 0.0 {
+            return 0.0;
+        }
+        let doc_len = *self.doc_lengths.get(pattern_id).unwrap_or(&0) as f64;
+
+        query_tokens.iter().map(|term| {
+            let Some(postings) = self.postings.get(term) else { return 0.0; };
+            let Some(&(_, freq)) = postings.iter().find(|(id, _)| id == pattern_id) else { return 0.0; };
+
+            let n_t = postings.len() as f64;
+            let idf = ((self.doc_count as f64 - n_t + 0.5) / (n_t + 0.5) + 1.0).ln();
+            let freq = freq as f64;
+            let denom = freq + BM25_K1 * (1.0 - BM25_B + BM25_B * doc_len / self.avgdl);
+
+            idf * (freq * (BM25_K1 + 1.0)) / denom
+        }).sum()
+    }
+}
+
+fn tokenize(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string())
+        .collect()
+}
+
+/// Min-max normalize a set of scores to [0, 1]. A degenerate (constant, or
+/// single-element) set can't be rescaled to a range, so each score is
+/// clamped to [0, 1] and returned as-is rather than collapsed to all zeros -
+/// a lone or tied candidate should still be judged on its raw score, not
+/// wiped out by normalization. Clamping matters here because BM25 scores
+/// aren't bounded to [0, 1] the way cosine similarity roughly is, and this
+/// result is blended directly into a [0, 1] hybrid/confidence score.
+fn min_max_normalize(scores: &[f64]) -> Vec<f64> {
+    let min = scores.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max = scores.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let range = max - min;
+    if range <= f64::EPSILON {
+        return scores.iter().map(|s| s.clamp(0.0, 1.0)).collect();
+    }
+    scores.iter().map(|s| (s - min) / range).collect()
+}
+
+/// Per-request classification knobs, threaded as one value through
+/// `classify`/`classify_with_embedding`/`find_patterns_in_subspace` instead
+/// of a long positional `f64`/`usize`/`Option` argument list - which made it
+/// easy to pass them in the wrong order as the list grew.
+#[derive(Debug, Clone)]
+pub struct ClassifyParams {
+    pub confidence_threshold: f64,
+    pub max_alternatives: usize,
+    pub semantic_ratio: f64,
+    pub filter_by_domain: Option<String>,
+}
+
 #[derive(Debug, Clone)]
 struct LevelSchema {
     pub id: String,
@@ -27,17 +127,30 @@ struct LevelSchema {
 pub struct HierarchicalClassifier {
     patterns: Arc<RwLock<HashMap<String, VectorPoint>>>,
     level_schemas: Arc<RwLock<Vec<LevelSchema>>>,
+    bm25_index: Arc<RwLock<Bm25Index>>,
+    /// HNSW index mirroring `patterns`, keyed by the original (unsanitized) pattern id.
+    patterns_ann: Arc<RwLock<HnswIndex>>,
+    /// HNSW index mirroring `level_schemas`, keyed by level-schema id.
+    level_schemas_ann: Arc<RwLock<HnswIndex>>,
     embedding_generator: Arc<EmbeddingGenerator>,
+    /// Live config snapshot, read through on each use so `/reload-config`
+    /// actually changes `auto_embed_write_back`/`ann_enabled`/`ann_ef_search`
+    /// rather than freezing them at startup.
+    config: Arc<RwLock<Config>>,
 }
 
 impl HierarchicalClassifier {
-    pub async fn new(_config: Config) -> Result<Self> {
+    pub async fn new(config: Arc<RwLock<Config>>) -> Result<Self> {
         info!("🔥 Initializing Hierarchical Classifier");
-        
+
         let classifier = Self {
             patterns: Arc::new(RwLock::new(HashMap::new())),
             level_schemas: Arc::new(RwLock::new(Vec::new())),
+            bm25_index: Arc::new(RwLock::new(Bm25Index::default())),
+            patterns_ann: Arc::new(RwLock::new(HnswIndex::new())),
+            level_schemas_ann: Arc::new(RwLock::new(HnswIndex::new())),
             embedding_generator: Arc::new(EmbeddingGenerator::new("all-MiniLM-L6-v2").await?),
+            config,
         };
         
         if let Err(e) = classifier.load_patterns_from_file("assets/patterns_with_embeddings.json").await {
@@ -50,75 +163,164 @@ impl HierarchicalClassifier {
         info!("✅ Hierarchical Classifier initialized");
         Ok(classifier)
     }
-    
+
+    /// Cloned snapshot of the live config, so a single lock acquisition can
+    /// back several field reads without them tearing across a concurrent
+    /// `/reload-config`.
+    fn config_snapshot(&self) -> Config {
+        self.config.read().map(|c| c.clone()).unwrap_or_else(|e| e.into_inner().clone())
+    }
+
     pub async fn load_patterns_from_file(&self, file_path: &str) -> Result<usize> {
         info!("📂 Loading patterns from: {}", file_path);
-        let pattern_data: Vec<Value> = serde_json::from_str(&std::fs::read_to_string(file_path)?)?;
-        
+        let mut pattern_data: Vec<Value> = serde_json::from_str(&std::fs::read_to_string(file_path)?)?;
+        let any_embeddings_computed = self.backfill_missing_embeddings(&mut pattern_data).await?;
+
         let mut patterns = self.patterns.write().map_err(|_| anyhow!("Lock failed"))?;
         patterns.clear();
-        
-        for pattern in pattern_data {
-            let id = pattern.get("id").and_then(|v| v.as_str()).ok_or_else(|| anyhow!("Missing pattern id"))?;
-            let embedding: Vec<f32> = pattern.get("embedding").and_then(|v| v.as_array())
-                .ok_or_else(|| anyhow!("Missing embedding"))?
-                .iter().map(|v| v.as_f64().unwrap_or(0.0) as f32).collect();
-            
-            if embedding.len() != 384 { continue; }
-            
+        let mut patterns_ann = self.patterns_ann.write().map_err(|_| anyhow!("Lock failed"))?;
+        *patterns_ann = HnswIndex::new();
+        let mut bm25_documents: HashMap<String, String> = HashMap::new();
+
+        for pattern in pattern_data.iter() {
+            let id = pattern.get("id").and_then(|v| v.as_str()).ok_or_else(|| anyhow!("Missing pattern id"))?.to_string();
+            let description = pattern.get("description").and_then(|v| v.as_str()).map(|s| s.to_string());
+            let embedding = read_embedding(pattern).ok_or_else(|| anyhow!("Pattern '{}' missing embedding after backfill", id))?;
+
             let mut payload = HashMap::new();
-            payload.insert("pattern_id".to_string(), Value::String(id.to_string()));
-            if let Some(desc) = pattern.get("description").and_then(|v| v.as_str()) {
-                payload.insert("description".to_string(), Value::String(desc.to_string()));
+            payload.insert("pattern_id".to_string(), Value::String(id.clone()));
+            let mut lexical_text = id.clone();
+            if let Some(desc) = &description {
+                payload.insert("description".to_string(), Value::String(desc.clone()));
+                lexical_text.push(' ');
+                lexical_text.push_str(desc);
             }
             if let Some(domain) = pattern.get("domain").and_then(|v| v.as_str()) {
                 payload.insert("domain".to_string(), Value::String(domain.to_string()));
             }
-            
+
+            bm25_documents.insert(id.clone(), lexical_text);
+            patterns_ann.insert(id.clone(), embedding.clone());
             patterns.insert(id.replace("/", "_"), VectorPoint { vector: embedding, payload });
         }
-        
+
         let count = patterns.len();
+        *self.bm25_index.write().map_err(|_| anyhow!("Lock failed"))? = Bm25Index::build(&bm25_documents);
         info!("✅ Loaded {} patterns", count);
+
+        if any_embeddings_computed && self.config_snapshot().auto_embed_write_back {
+            info!("💾 Writing auto-computed pattern embeddings back to {}", file_path);
+            std::fs::write(file_path, serde_json::to_string_pretty(&pattern_data)?)?;
+        }
+
         Ok(count)
     }
-    
+
     pub async fn load_level_schemas(&self, file_path: &str) -> Result<usize> {
         info!("📂 Loading level schemas from: {}", file_path);
-        let level_data: Vec<Value> = serde_json::from_str(&std::fs::read_to_string(file_path)?)?;
-        
+        let mut level_data: Vec<Value> = serde_json::from_str(&std::fs::read_to_string(file_path)?)?;
+        let any_embeddings_computed = self.backfill_missing_embeddings(&mut level_data).await?;
+
         let mut schemas = self.level_schemas.write().map_err(|_| anyhow!("Lock failed"))?;
         schemas.clear();
-        
-        for item in level_data {
-            let id = item.get("id").and_then(|v| v.as_str()).ok_or_else(|| anyhow!("Missing id"))?;
-            let level = item.get("level").and_then(|v| v.as_str()).unwrap_or("unknown");
+        let mut level_schemas_ann = self.level_schemas_ann.write().map_err(|_| anyhow!("Lock failed"))?;
+        *level_schemas_ann = HnswIndex::new();
+
+        for item in level_data.iter() {
+            let id = item.get("id").and_then(|v| v.as_str()).ok_or_else(|| anyhow!("Missing id"))?.to_string();
+            let level = item.get("level").and_then(|v| v.as_str()).unwrap_or("unknown").to_string();
             let parent_id = item.get("parent_id").and_then(|v| v.as_str()).map(|s| s.to_string());
-            let embedding: Vec<f32> = item.get("embedding").and_then(|v| v.as_array())
-                .ok_or_else(|| anyhow!("Missing embedding"))?
-                .iter().map(|v| v.as_f64().unwrap_or(0.0) as f32).collect();
-            
-            if embedding.len() != 384 { continue; }
-            
-            schemas.push(LevelSchema { id: id.to_string(), level: level.to_string(), parent_id, embedding });
+            let embedding = read_embedding(item).ok_or_else(|| anyhow!("Level schema '{}' missing embedding after backfill", id))?;
+
+            level_schemas_ann.insert(id.clone(), embedding.clone());
+            schemas.push(LevelSchema { id, level, parent_id, embedding });
         }
-        
+
         let count = schemas.len();
         info!("✅ Loaded {} level schemas", count);
+
+        if any_embeddings_computed && self.config_snapshot().auto_embed_write_back {
+            info!("💾 Writing auto-computed level schema embeddings back to {}", file_path);
+            std::fs::write(file_path, serde_json::to_string_pretty(&level_data)?)?;
+        }
+
         Ok(count)
     }
-    
-    pub async fn classify(&self, text: &str, confidence_threshold: f64, max_alternatives: usize) -> Result<(Option<PatternMatch>, Vec<PatternMatch>, Vec<String>)> {
-        let mut steps = Vec::new();
+
+    /// Backfill missing/malformed `embedding` fields on `entries` in a single
+    /// batched forward pass rather than one `encode` call per entry - the
+    /// difference between a near-instant reload and thousands of sequential
+    /// BERT passes on a large pattern/level-schema file. Each entry (pattern or
+    /// level schema) embeds its `description`, falling back to its `id`.
+    /// Returns whether any embedding was computed.
+    async fn backfill_missing_embeddings(&self, entries: &mut [Value]) -> Result<bool> {
+        let missing: Vec<usize> = entries.iter()
+            .enumerate()
+            .filter(|(_, entry)| read_embedding(entry).is_none())
+            .map(|(i, _)| i)
+            .collect();
+
+        if missing.is_empty() {
+            return Ok(false);
+        }
+
+        let texts: Vec<&str> = missing.iter()
+            .map(|&i| {
+                let entry = &entries[i];
+                entry.get("description").and_then(|v| v.as_str())
+                    .or_else(|| entry.get("id").and_then(|v| v.as_str()))
+                    .unwrap_or("")
+            })
+            .collect();
+
+        debug!("🧠 Auto-embedding {} entr(ies) missing embeddings in one batched pass", missing.len());
+        let computed = self.embedding_generator.encode_batch(&texts).await?;
+
+        for (&i, embedding) in missing.iter().zip(computed.iter()) {
+            write_embedding(&mut entries[i], embedding);
+        }
+
+        Ok(true)
+    }
+
+    pub async fn classify(&self, text: &str, params: &ClassifyParams) -> Result<(Option<PatternMatch>, Vec<PatternMatch>, Vec<String>)> {
         let embedding = self.embedding_generator.encode(text).await?;
+        self.classify_with_embedding(embedding, text, params).await
+    }
+
+    /// Classify from an already-computed embedding, skipping the encode
+    /// step. Used by the hot-path embedding batcher, which encodes a whole
+    /// batch of requests in a single forward pass up front. `params.filter_by_domain`,
+    /// when set, pins the top-level domain instead of letting it be picked by
+    /// similarity - classification fails with no match if the requested
+    /// domain doesn't score at all for this text.
+    pub async fn classify_with_embedding(
+        &self,
+        embedding: Vec<f32>,
+        text: &str,
+        params: &ClassifyParams,
+    ) -> Result<(Option<PatternMatch>, Vec<PatternMatch>, Vec<String>)> {
+        let mut steps = Vec::new();
+        let filter_by_domain = params.filter_by_domain.as_deref();
 
         // Step 1 & 2 & 3: Find the best hierarchical path (Domain -> Area -> Topic)
         let domain_candidates = self.classify_at_level(&embedding, "domain", None).await?;
         if domain_candidates.is_empty() { return Ok((None, vec![], vec!["No domain matches found.".into()])); }
-        let (best_domain, domain_conf) = &domain_candidates[0];
-        steps.push(format!("✅ Domain: {} ({:.1}%)", best_domain, domain_conf * 100.0));
 
-        let area_candidates = self.classify_at_level(&embedding, "area", Some(best_domain)).await?;
+        let (best_domain, domain_conf) = match filter_by_domain {
+            Some(domain) => match domain_candidates.iter().find(|(id, _)| id == domain) {
+                Some((id, conf)) => (id.clone(), *conf),
+                None => {
+                    steps.push(format!("❌ Requested domain '{}' has no matches for this text.", domain));
+                    return Ok((None, vec![], steps));
+                }
+            },
+            None => domain_candidates[0].clone(),
+        };
+        let domain_note = if filter_by_domain.is_some() { " [forced by filter_by_domain]" } else { "" };
+        steps.push(format!("✅ Domain: {} ({:.1}%){}", best_domain, domain_conf * 100.0, domain_note));
+
+        let area_candidates = self.classify_at_level(&embedding, "area", Some(&best_domain)).await?;
         if area_candidates.is_empty() { return Ok((None, vec![], steps)); }
         let (best_area, area_conf) = &area_candidates[0];
         steps.push(format!("✅ Area: {} ({:.1}%)", best_area, area_conf * 100.0));
@@ -127,20 +329,20 @@ impl HierarchicalClassifier {
         if topic_candidates.is_empty() { return Ok((None, vec![], steps)); }
         let (best_topic, topic_conf) = &topic_candidates[0];
         steps.push(format!("✅ Topic: {} ({:.1}%)", best_topic, topic_conf * 100.0));
-        
+
         // Step 4: Run vector search ONLY within the identified subspace
         let pattern_prefix = format!("{}/{}/{}", best_domain, best_area, best_topic);
-        let final_candidates = self.find_patterns_in_subspace(&embedding, &pattern_prefix, confidence_threshold, max_alternatives).await?;
+        let final_candidates = self.find_patterns_in_subspace(&embedding, text, &pattern_prefix, params, &mut steps).await?;
 
         if final_candidates.is_empty() {
-            steps.push(format!("❌ No final pattern matches found under '{}' with threshold > {:.1}%", pattern_prefix, confidence_threshold * 100.0));
+            steps.push(format!("❌ No final pattern matches found under '{}' with threshold > {:.1}%", pattern_prefix, params.confidence_threshold * 100.0));
             return Ok((None, Vec::new(), steps));
         }
 
         // Step 5: Apply confidence weighting to the results from the correct subspace
-        let mut results: Vec<PatternMatch> = final_candidates.into_iter().map(|(pattern_id, pattern_similarity, _point)| {
-            // New confidence: pattern's cosine score blended with the confidence of the hierarchical path.
-            let final_confidence = pattern_similarity * (domain_conf * 0.4 + area_conf * 0.3 + topic_conf * 0.3);
+        let mut results: Vec<PatternMatch> = final_candidates.into_iter().map(|(pattern_id, hybrid_score, _point)| {
+            // New confidence: hybrid (lexical+vector) score blended with the confidence of the hierarchical path.
+            let final_confidence = hybrid_score * (domain_conf * 0.4 + area_conf * 0.3 + topic_conf * 0.3);
             PatternMatch {
                 pattern_id,
                 confidence: final_confidence,
@@ -158,48 +360,139 @@ impl HierarchicalClassifier {
     
     async fn classify_at_level(&self, embedding: &[f32], level: &str, parent_filter: Option<&str>) -> Result<Vec<(String, f64)>> {
         let schemas = self.level_schemas.read().map_err(|_| anyhow!("Lock failed"))?;
-        let mut scores: Vec<(String, f64)> = schemas.iter()
-            .filter(|s| s.level == level)
-            .filter(|s| parent_filter.map_or(true, |p| s.parent_id.as_deref() == Some(p)))
-            .map(|s| {
-                let similarity = cosine_similarity(embedding, &s.embedding) as f64;
-                (s.id.clone(), similarity)
-            })
-            .collect();
-        
+
+        let matches_level = |s: &&LevelSchema| {
+            s.level == level && parent_filter.map_or(true, |p| s.parent_id.as_deref() == Some(p))
+        };
+
+        let config = self.config_snapshot();
+        let mut scores: Vec<(String, f64)> = if config.ann_enabled {
+            let ann = self.level_schemas_ann.read().map_err(|_| anyhow!("Lock failed"))?;
+            // Over-fetch a small multiple of the matching level/parent subset
+            // size, not the whole schema table. Counting that subset and
+            // mapping each hit back to its schema still scan `schemas`
+            // linearly, so this path is linear in the total schema count.
+            let subset_size = schemas.iter().filter(matches_level).count();
+            let overfetch = (subset_size * 4).max(config.ann_ef_search);
+            ann.search(embedding, overfetch, config.ann_ef_search).into_iter()
+                .filter_map(|(id, sim)| {
+                    let schema = schemas.iter().find(|s| s.id == id)?;
+                    matches_level(&schema).then(|| (id, sim as f64))
+                })
+                .collect()
+        } else {
+            schemas.iter()
+                .filter(matches_level)
+                .map(|s| {
+                    let similarity = cosine_similarity(embedding, &s.embedding) as f64;
+                    (s.id.clone(), similarity)
+                })
+                .collect()
+        };
+
         scores.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
         Ok(scores)
     }
     
-    async fn find_patterns_in_subspace(&self, embedding: &[f32], prefix: &str, threshold: f64, limit: usize) -> Result<Vec<(String, f64, VectorPoint)>> {
+    async fn find_patterns_in_subspace(
+        &self,
+        embedding: &[f32],
+        query_text: &str,
+        prefix: &str,
+        params: &ClassifyParams,
+        steps: &mut Vec<String>,
+    ) -> Result<Vec<(String, f64, VectorPoint)>> {
+        let threshold = params.confidence_threshold;
+        let limit = params.max_alternatives;
+        let semantic_ratio = params.semantic_ratio;
         let patterns = self.patterns.read().map_err(|_| anyhow!("Lock failed"))?;
+        let bm25_index = self.bm25_index.read().map_err(|_| anyhow!("Lock failed"))?;
         let replaced_prefix = prefix.replace("/", "_");
-        
-        info!(target: "classifier", "Searching for patterns with prefix: '{}', threshold: {}", prefix, threshold);
-
-        let mut results: Vec<(String, f64, VectorPoint)> = patterns.iter()
-            .filter(|(id, _)| id.starts_with(&replaced_prefix))
-            .filter_map(|(_, point)| {
-                let similarity = cosine_similarity(embedding, &point.vector) as f64;
-                
-                let pattern_id = point.payload.get("pattern_id").and_then(|v| v.as_str()).unwrap_or("unknown");
-                debug!(target: "classifier", "Pattern: {}, Similarity: {:.4}", pattern_id, similarity);
-
-                if similarity >= threshold {
-                    Some((pattern_id.to_string(), similarity, point.clone()))
-                } else { 
-                    None 
-                }
-            })
-            .collect();
-            
+        let query_tokens = tokenize(query_text);
+
+        let config = self.config_snapshot();
+        info!(target: "classifier", "Searching for patterns with prefix: '{}', threshold: {}, semantic_ratio: {}, ann_enabled: {}", prefix, threshold, semantic_ratio, config.ann_enabled);
+
+        let candidates: Vec<(String, f64, f64, VectorPoint)> = if config.ann_enabled {
+            let ann = self.patterns_ann.read().map_err(|_| anyhow!("Lock failed"))?;
+            // Search is confined to a domain/area/topic prefix subspace, which the
+            // index doesn't know about, so over-fetch and post-filter by id. Scale
+            // the overfetch off the matching subset's size, not the whole corpus,
+            // the same way classify_at_level does - a flat multiple of `limit`
+            // silently loses recall as the pattern count grows relative to a
+            // given subspace.
+            let subset_size = patterns.keys().filter(|id| id.starts_with(&replaced_prefix)).count();
+            let overfetch = (subset_size * 4).max(config.ann_ef_search);
+            ann.search(embedding, overfetch, config.ann_ef_search).into_iter()
+                .filter(|(id, _)| id.starts_with(prefix))
+                .filter_map(|(pattern_id, cosine)| {
+                    let point = patterns.get(&pattern_id.replace("/", "_"))?.clone();
+                    let bm25 = bm25_index.score(&query_tokens, &pattern_id);
+                    Some((pattern_id, cosine as f64, bm25, point))
+                })
+                .collect()
+        } else {
+            patterns.iter()
+                .filter(|(id, _)| id.starts_with(&replaced_prefix))
+                .map(|(_, point)| {
+                    let cosine = cosine_similarity(embedding, &point.vector) as f64;
+                    let pattern_id = point.payload.get("pattern_id").and_then(|v| v.as_str()).unwrap_or("unknown").to_string();
+                    let bm25 = bm25_index.score(&query_tokens, &pattern_id);
+                    (pattern_id, cosine, bm25, point.clone())
+                })
+                .collect()
+        };
+
+        if candidates.is_empty() {
+            info!(target: "classifier", "Found 0 patterns matching prefix '{}'", prefix);
+            return Ok(Vec::new());
+        }
+
+        let cosine_scores: Vec<f64> = candidates.iter().map(|(_, c, _, _)| *c).collect();
+        let bm25_scores: Vec<f64> = candidates.iter().map(|(_, _, b, _)| *b).collect();
+        let cosine_norm = min_max_normalize(&cosine_scores);
+        let bm25_norm = min_max_normalize(&bm25_scores);
+
+        // Per-candidate component scores, kept alongside the filtered results so
+        // classification_steps can surface the full ranking for debugging - not
+        // just the winner's summary line.
+        let mut component_scores: Vec<(String, f64, f64, f64)> = Vec::with_capacity(candidates.len());
+        let mut results: Vec<(String, f64, VectorPoint)> = Vec::new();
+
+        for (i, (pattern_id, cosine, bm25, point)) in candidates.into_iter().enumerate() {
+            let hybrid = semantic_ratio * cosine_norm[i] + (1.0 - semantic_ratio) * bm25_norm[i];
+            debug!(target: "classifier", "Pattern: {}, cosine: {:.4}, bm25: {:.4}, hybrid: {:.4}", pattern_id, cosine, bm25, hybrid);
+            component_scores.push((pattern_id.clone(), cosine, bm25, hybrid));
+
+            if hybrid >= threshold {
+                results.push((pattern_id, hybrid, point));
+            }
+        }
+
         info!(target: "classifier", "Found {} patterns matching prefix '{}' above threshold", results.len(), prefix);
 
         results.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
         results.truncate(limit);
+
+        component_scores.sort_by(|a, b| b.3.partial_cmp(&a.3).unwrap_or(std::cmp::Ordering::Equal));
+        steps.push(format!("🔎 Hybrid scoring (ratio={:.2}) under '{}':", semantic_ratio, prefix));
+        for (rank, (pattern_id, cosine, bm25, hybrid)) in component_scores.iter().enumerate() {
+            steps.push(format!(
+                "   {}. {} cosine={:.3} bm25={:.3} hybrid={:.3}",
+                rank + 1, pattern_id, cosine, bm25, hybrid
+            ));
+        }
+
         Ok(results)
     }
 
+    /// Batch-encode texts in a single forward pass; exposed so the hot-path
+    /// embedding batcher can coalesce several requests' embeddings without
+    /// reaching into `embedding_generator` directly.
+    pub async fn encode_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        self.embedding_generator.encode_batch(texts).await
+    }
+
     pub async fn health_check(&self) -> Result<()> {
         let count = self.patterns.read().map_err(|_| anyhow!("Lock failed"))?.len();
         info!("📊 Classifier healthy - {} patterns loaded", count);
@@ -207,6 +500,20 @@ impl HierarchicalClassifier {
     }
 }
 
+/// Read a 384-dim `embedding` array off a JSON entry, if present and valid.
+fn read_embedding(entry: &Value) -> Option<Vec<f32>> {
+    let embedding: Vec<f32> = entry.get("embedding")?.as_array()?
+        .iter().map(|v| v.as_f64().unwrap_or(0.0) as f32).collect();
+    if embedding.len() == 384 { Some(embedding) } else { None }
+}
+
+/// Set/overwrite the `embedding` field on a JSON entry with a freshly computed vector.
+fn write_embedding(entry: &mut Value, embedding: &[f32]) {
+    if let Some(obj) = entry.as_object_mut() {
+        obj.insert("embedding".to_string(), serde_json::json!(embedding));
+    }
+}
+
 fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
     if a.len() != b.len() { return 0.0; }
     let dot_product: f32 = a.iter().zip(b.iter()).map(|(x, y)| x * y).sum();
@@ -1,20 +1,62 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Config {
     #[serde(default)]
     pub collection_name: String,
-    
+
     #[serde(default)]
     pub confidence_threshold: f64,
-    
+
     #[serde(default)]
     pub max_alternatives: i32,
-    
+
     #[serde(default)]
     pub port: u16,
+
+    /// When loading patterns/level schemas without a precomputed embedding,
+    /// write the on-the-fly computed embedding back to the source file.
+    #[serde(default)]
+    pub auto_embed_write_back: bool,
+
+    /// Use the HNSW approximate index for similarity search instead of the
+    /// brute-force cosine scan. Disable to trade latency for exact recall.
+    #[serde(default = "default_ann_enabled")]
+    pub ann_enabled: bool,
+
+    /// Size of the dynamic candidate list during HNSW search; higher trades
+    /// latency for recall.
+    #[serde(default = "default_ann_ef_search")]
+    pub ann_ef_search: usize,
+
+    /// HTTP insert endpoint for the columnar telemetry sink (e.g. a
+    /// ClickHouse `INSERT ... FORMAT JSONEachRow` URL). Telemetry is
+    /// disabled when unset.
+    #[serde(default)]
+    pub telemetry_endpoint: Option<String>,
+
+    /// Max requests coalesced into one batched embedding pass on the hot
+    /// path before a flush is forced.
+    #[serde(default = "default_embed_batch_size")]
+    pub embed_batch_size: usize,
+
+    /// Max time (ms) a request waits in the batch buffer before a partial
+    /// batch is flushed anyway.
+    #[serde(default = "default_embed_max_batch_latency_ms")]
+    pub embed_max_batch_latency_ms: u64,
+
+    /// Capture a symbolicated backtrace with each reported classify-path
+    /// failure. Off by default since capturing/demangling a backtrace on
+    /// every error isn't free; turn on for debugging.
+    #[serde(default)]
+    pub capture_error_backtraces: bool,
 }
 
+fn default_ann_enabled() -> bool { true }
+fn default_ann_ef_search() -> usize { 64 }
+fn default_embed_batch_size() -> usize { 16 }
+fn default_embed_max_batch_latency_ms() -> u64 { 10 }
+
 impl Default for Config {
     fn default() -> Self {
         Self {
@@ -22,27 +64,136 @@ impl Default for Config {
             confidence_threshold: 0.5,
             max_alternatives: 3,
             port: 3000,
+            auto_embed_write_back: false,
+            ann_enabled: default_ann_enabled(),
+            ann_ef_search: default_ann_ef_search(),
+            telemetry_endpoint: None,
+            embed_batch_size: default_embed_batch_size(),
+            embed_max_batch_latency_ms: default_embed_max_batch_latency_ms(),
+            capture_error_backtraces: false,
+        }
+    }
+}
+
+/// File-layer view of `Config`: every field optional so a config file only
+/// needs to specify what it overrides from the built-in defaults.
+#[derive(Debug, Default, Deserialize)]
+struct PartialConfig {
+    collection_name: Option<String>,
+    confidence_threshold: Option<f64>,
+    max_alternatives: Option<i32>,
+    port: Option<u16>,
+    auto_embed_write_back: Option<bool>,
+    ann_enabled: Option<bool>,
+    ann_ef_search: Option<usize>,
+    telemetry_endpoint: Option<String>,
+    embed_batch_size: Option<usize>,
+    embed_max_batch_latency_ms: Option<u64>,
+    capture_error_backtraces: Option<bool>,
+}
+
+impl PartialConfig {
+    fn apply_onto(self, base: Config) -> Config {
+        Config {
+            collection_name: self.collection_name.unwrap_or(base.collection_name),
+            confidence_threshold: self.confidence_threshold.unwrap_or(base.confidence_threshold),
+            max_alternatives: self.max_alternatives.unwrap_or(base.max_alternatives),
+            port: self.port.unwrap_or(base.port),
+            auto_embed_write_back: self.auto_embed_write_back.unwrap_or(base.auto_embed_write_back),
+            ann_enabled: self.ann_enabled.unwrap_or(base.ann_enabled),
+            ann_ef_search: self.ann_ef_search.unwrap_or(base.ann_ef_search),
+            telemetry_endpoint: self.telemetry_endpoint.or(base.telemetry_endpoint),
+            embed_batch_size: self.embed_batch_size.unwrap_or(base.embed_batch_size),
+            embed_max_batch_latency_ms: self.embed_max_batch_latency_ms.unwrap_or(base.embed_max_batch_latency_ms),
+            capture_error_backtraces: self.capture_error_backtraces.unwrap_or(base.capture_error_backtraces),
         }
     }
 }
 
 impl Config {
+    /// Defaults layered with environment variables only - kept for callers
+    /// that don't need the file layer.
     pub fn from_env() -> Self {
+        Self::apply_env_layer(Self::default())
+    }
+
+    /// Layered load, in precedence order: built-in defaults -> config file
+    /// (TOML or JSON, path from `CONFIG_PATH`) -> environment variables.
+    /// This is what `/reload-config` re-runs to pick up file/env changes
+    /// without a restart.
+    pub fn load() -> anyhow::Result<Self> {
+        let mut config = Self::default();
+        if let Ok(path) = std::env::var("CONFIG_PATH") {
+            config = Self::apply_file_layer(config, &path)?;
+        }
+        Ok(Self::apply_env_layer(config))
+    }
+
+    fn apply_file_layer(base: Self, path: &str) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read config file '{}': {}", path, e))?;
+
+        let partial: PartialConfig = if path.ends_with(".toml") {
+            toml::from_str(&contents)
+                .map_err(|e| anyhow::anyhow!("Failed to parse TOML config '{}': {}", path, e))?
+        } else {
+            serde_json::from_str(&contents)
+                .map_err(|e| anyhow::anyhow!("Failed to parse JSON config '{}': {}", path, e))?
+        };
+
+        Ok(partial.apply_onto(base))
+    }
+
+    fn apply_env_layer(base: Self) -> Self {
         use std::env;
         Self {
-            collection_name: env::var("COLLECTION_NAME").unwrap_or_else(|_| "pattern_index".to_string()),
+            collection_name: env::var("COLLECTION_NAME").unwrap_or(base.collection_name),
             confidence_threshold: env::var("CONFIDENCE_THRESHOLD")
-                .unwrap_or_else(|_| "0.5".to_string())
-                .parse()
-                .unwrap_or(0.5),
+                .ok().and_then(|v| v.parse().ok())
+                .unwrap_or(base.confidence_threshold),
             max_alternatives: env::var("MAX_ALTERNATIVES")
-                .unwrap_or_else(|_| "3".to_string())
-                .parse()
-                .unwrap_or(3),
+                .ok().and_then(|v| v.parse().ok())
+                .unwrap_or(base.max_alternatives),
             port: env::var("PORT")
-                .unwrap_or_else(|_| "3000".to_string())
-                .parse()
-                .unwrap_or(3000),
+                .ok().and_then(|v| v.parse().ok())
+                .unwrap_or(base.port),
+            auto_embed_write_back: env::var("AUTO_EMBED_WRITE_BACK")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(base.auto_embed_write_back),
+            ann_enabled: env::var("ANN_ENABLED")
+                .map(|v| v != "false" && v != "0")
+                .unwrap_or(base.ann_enabled),
+            ann_ef_search: env::var("ANN_EF_SEARCH")
+                .ok().and_then(|v| v.parse().ok())
+                .unwrap_or(base.ann_ef_search),
+            telemetry_endpoint: env::var("TELEMETRY_ENDPOINT").ok().or(base.telemetry_endpoint),
+            embed_batch_size: env::var("EMBED_BATCH_SIZE")
+                .ok().and_then(|v| v.parse().ok())
+                .unwrap_or(base.embed_batch_size),
+            embed_max_batch_latency_ms: env::var("EMBED_MAX_BATCH_LATENCY_MS")
+                .ok().and_then(|v| v.parse().ok())
+                .unwrap_or(base.embed_max_batch_latency_ms),
+            capture_error_backtraces: env::var("CAPTURE_ERROR_BACKTRACES")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(base.capture_error_backtraces),
+        }
+    }
+
+    /// Diff two configs field-by-field, for reporting what changed on reload.
+    pub fn diff(old: &Config, new: &Config) -> serde_json::Value {
+        let old_value = serde_json::to_value(old).unwrap_or_default();
+        let new_value = serde_json::to_value(new).unwrap_or_default();
+        let mut changed = serde_json::Map::new();
+
+        if let (Some(old_map), Some(new_map)) = (old_value.as_object(), new_value.as_object()) {
+            for (key, new_field) in new_map {
+                let old_field = old_map.get(key).cloned().unwrap_or(serde_json::Value::Null);
+                if &old_field != new_field {
+                    changed.insert(key.clone(), serde_json::json!({ "old": old_field, "new": new_field }));
+                }
+            }
         }
+
+        serde_json::Value::Object(changed)
     }
-}
\ No newline at end of file
+}
@@ -56,47 +56,61 @@ impl EmbeddingGenerator {
     
     /// Generate embedding for text using Candle BERT
     pub async fn encode(&self, text: &str) -> Result<Vec<f32>> {
-        // Tokenize input
-        let encoding = self.tokenizer
-            .encode(text, true)
-            .map_err(|e| anyhow!("Tokenization failed: {}", e))?;
-        
-        let tokens = encoding.get_ids();
-        let token_ids = Tensor::new(tokens, &self.device)?;
+        self.encode_batch(&[text]).await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("encode_batch returned no embeddings for a single input"))
+    }
+
+    /// Generate embeddings for a batch of texts in a single forward pass.
+    ///
+    /// Each sequence is padded to the batch's max token length (with the
+    /// tokenizer's pad id) and a real 0/1 attention mask excludes the padded
+    /// positions from mean pooling, so results are identical to calling
+    /// `encode` on each text individually - just much faster in bulk.
+    pub async fn encode_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let encodings = self.tokenizer
+            .encode_batch(texts.to_vec(), true)
+            .map_err(|e| anyhow!("Batch tokenization failed: {}", e))?;
+
+        let pad_id = self.tokenizer.get_padding().map(|p| p.pad_id).unwrap_or(0);
+        let max_len = encodings.iter().map(|e| e.get_ids().len()).max().unwrap_or(0);
+        let batch_size = texts.len();
+
+        let mut padded_ids = Vec::with_capacity(batch_size * max_len);
+        let mut attention_mask_values = Vec::with_capacity(batch_size * max_len);
+
+        for encoding in &encodings {
+            let ids = encoding.get_ids();
+            padded_ids.extend_from_slice(ids);
+            padded_ids.extend(std::iter::repeat(pad_id).take(max_len - ids.len()));
+
+            attention_mask_values.extend(std::iter::repeat(1.0f32).take(ids.len()));
+            attention_mask_values.extend(std::iter::repeat(0.0f32).take(max_len - ids.len()));
+        }
+
+        let token_ids = Tensor::from_vec(padded_ids, (batch_size, max_len), &self.device)?;
         let token_type_ids = token_ids.zeros_like()?;
-        
-        // Create attention mask (all 1s for our tokens) as F32
-        let attention_mask = Tensor::ones(token_ids.shape(), DType::F32, &self.device)?;
-        
-        // Add batch dimension
-        let token_ids = token_ids.unsqueeze(0)?;
-        let token_type_ids = token_type_ids.unsqueeze(0)?;
-        let attention_mask = attention_mask.unsqueeze(0)?;
-        
-        // Forward pass through BERT
+        let attention_mask = Tensor::from_vec(attention_mask_values, (batch_size, max_len), &self.device)?;
+
+        // Forward pass through BERT, once for the whole batch
         let sequence_output = self.model.forward(&token_ids, &token_type_ids, Some(&attention_mask))?;
-        
-        // Mean pooling over sequence length (excluding padding)
-        // Multiply by attention mask to zero out padding positions
+
+        // Mean pooling over sequence length, excluding padded positions
         let attention_mask_expanded = attention_mask.unsqueeze(2)?;
         let masked_output = sequence_output.broadcast_mul(&attention_mask_expanded)?;
-        
-        // Sum over sequence length
         let sum_embeddings = masked_output.sum(1)?;
-        
-        // Get the sum of attention mask for normalization
         let sum_mask = attention_mask.sum(1)?;
         let sum_mask_expanded = sum_mask.unsqueeze(1)?;
-        
-        // Compute mean pooling
         let mean_embeddings = sum_embeddings.broadcast_div(&sum_mask_expanded)?;
-        
-        // Convert to Vec<f32>
-        let embedding_vec = mean_embeddings.squeeze(0)?.to_vec1::<f32>()?;
-        
-        Ok(embedding_vec)
+
+        Ok(mean_embeddings.to_vec2::<f32>()?)
     }
-    
+
     /// Get embedding dimension
     pub fn embedding_dim(&self) -> usize {
         self.embedding_dim
@@ -134,6 +148,24 @@ mod tests {
         // Similar sentences should have higher similarity than dissimilar ones
         assert!(sim_12 > sim_13);
     }
+
+    #[tokio::test]
+    async fn test_batch_encoding_matches_single() {
+        let generator = EmbeddingGenerator::new("all-MiniLM-L6-v2").await.unwrap();
+
+        let texts = ["short", "a somewhat longer sentence than the others"];
+        let batch_embeddings = generator.encode_batch(&texts).await.unwrap();
+        assert_eq!(batch_embeddings.len(), texts.len());
+
+        for (text, batch_embedding) in texts.iter().zip(batch_embeddings.iter()) {
+            assert_eq!(batch_embedding.len(), 384);
+            let single_embedding = generator.encode(text).await.unwrap();
+            // Padding/truncation of the shorter sequences in the batch should not
+            // change the pooled embedding of any individual sequence.
+            let sim = cosine_similarity(&single_embedding, batch_embedding);
+            assert!(sim > 0.999, "batch vs single encoding diverged: {}", sim);
+        }
+    }
 }
 
 fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
@@ -0,0 +1,109 @@
+//! Structured error capture for the classify path.
+//!
+//! A bare `error_message` string loses the context needed to diagnose an
+//! embedding/search/reload failure after the fact. [`ErrorReporter`]
+//! captures the failing stage, the input's text length, the `request_id`,
+//! and - when `capture_backtraces` is enabled - a symbolicated backtrace,
+//! then forwards the result to the telemetry sink and the in-memory log
+//! buffer (via a normal `tracing::error!` call, so it lands in
+//! [`crate::logs::LogBuffer`] automatically). Repeated failures with the
+//! same stage and message bump an occurrence counter rather than
+//! re-recording in full each time, so a hot failure loop can't flood either
+//! store - but the updated count is still periodically re-sent to telemetry
+//! (every [`REEMIT_INTERVAL`]th occurrence) so offline analysis on the
+//! columnar sink doesn't see a count frozen at 1 forever.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use tracing::error;
+
+use crate::config::Config;
+use crate::telemetry::{ErrorEvent, TelemetrySink};
+
+/// Re-send the updated occurrence count to telemetry every this-many
+/// repeats of the same (stage, message) failure, instead of only on first
+/// sight - keeps a hot failure loop from flooding the sink while still
+/// letting its `occurrences` count advance over time.
+const REEMIT_INTERVAL: u64 = 20;
+
+/// Which phase of the classify path a failure occurred in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClassificationStage {
+    Embedding,
+    Search,
+    Reload,
+}
+
+impl ClassificationStage {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ClassificationStage::Embedding => "embedding",
+            ClassificationStage::Search => "search",
+            ClassificationStage::Reload => "reload",
+        }
+    }
+}
+
+/// Captures classify-path failures into the telemetry sink and log buffer.
+pub struct ErrorReporter {
+    telemetry: TelemetrySink,
+    /// Live config snapshot, so `/reload-config` can flip
+    /// `capture_error_backtraces` without a restart.
+    config: Arc<RwLock<Config>>,
+    /// (stage, message) -> occurrence count, so repeats of the same
+    /// failure only increment a counter instead of re-recording in full.
+    seen: Mutex<HashMap<(&'static str, String), u64>>,
+}
+
+impl ErrorReporter {
+    pub fn new(telemetry: TelemetrySink, config: Arc<RwLock<Config>>) -> Self {
+        Self { telemetry, config, seen: Mutex::new(HashMap::new()) }
+    }
+
+    /// Record a failure at `stage` for `request_id`. `text_len` is the
+    /// length of the `WeaveUnit` text that was being classified, 0 if not
+    /// applicable (e.g. a pattern reload).
+    pub fn report(&self, stage: ClassificationStage, request_id: &str, text_len: usize, message: &str) {
+        let occurrences = {
+            let mut seen = self.seen.lock().unwrap_or_else(|e| e.into_inner());
+            let count = seen.entry((stage.as_str(), message.to_string())).or_insert(0);
+            *count += 1;
+            *count
+        };
+
+        if occurrences > 1 {
+            if occurrences % REEMIT_INTERVAL != 0 {
+                return;
+            }
+            error!(target: "classify_error", stage = stage.as_str(), request_id, "{} (seen {} times)", message, occurrences);
+        } else {
+            error!(target: "classify_error", stage = stage.as_str(), request_id, "{}", message);
+        }
+
+        let capture_backtraces = self.config.read().map(|c| c.capture_error_backtraces).unwrap_or_else(|e| e.into_inner().capture_error_backtraces);
+        let backtrace = if capture_backtraces { Some(demangled_backtrace()) } else { None };
+
+        self.telemetry.record_error(ErrorEvent {
+            request_id: request_id.to_string(),
+            stage: stage.as_str().to_string(),
+            text_len,
+            message: message.to_string(),
+            backtrace,
+            occurrences,
+        });
+    }
+}
+
+/// Capture the current backtrace and demangle each frame's symbol name.
+fn demangled_backtrace() -> String {
+    let mut frames = Vec::new();
+    backtrace::trace(|frame| {
+        backtrace::resolve_frame(frame, |symbol| {
+            if let Some(name) = symbol.name() {
+                frames.push(format!("{:#}", rustc_demangle::demangle(&name.to_string())));
+            }
+        });
+        true
+    });
+    frames.join("\n")
+}
@@ -74,6 +74,10 @@ pub struct ClassificationRequest {
     pub confidence_threshold: f64,
     #[serde(default)]
     pub filter_by_domain: Option<String>,
+    /// Blend factor between lexical (BM25) and vector similarity when ranking
+    /// final pattern matches: 0.0 = pure keyword, 1.0 = pure vector.
+    #[serde(default = "default_semantic_ratio")]
+    pub semantic_ratio: f64,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -116,6 +120,7 @@ pub struct IndexBuildConfig {
 // Helper functions for defaults
 fn default_max_alternatives() -> i32 { 3 }
 fn default_confidence_threshold() -> f64 { 0.5 }
+fn default_semantic_ratio() -> f64 { 1.0 }
 fn default_status() -> String { "success".to_string() }
 fn default_model_name() -> String { "all-MiniLM-L6-v2".to_string() }
 fn default_qdrant_host() -> String { "localhost".to_string() }
@@ -186,6 +191,7 @@ impl ClassificationRequest {
             max_alternatives: default_max_alternatives(),
             confidence_threshold: default_confidence_threshold(),
             filter_by_domain: None,
+            semantic_ratio: default_semantic_ratio(),
         }
     }
 
@@ -196,6 +202,7 @@ impl ClassificationRequest {
             max_alternatives: default_max_alternatives(),
             confidence_threshold: default_confidence_threshold(),
             filter_by_domain: Some(domain),
+            semantic_ratio: default_semantic_ratio(),
         }
     }
 }
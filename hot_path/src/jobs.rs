@@ -0,0 +1,241 @@
+//! Background job runner for asynchronous classification.
+//!
+//! `POST /classify/async` enqueues a request and returns immediately with
+//! a `request_id` and status `"queued"`; a bounded pool of worker tasks
+//! drains the queue, classifies in the background, and stores the result
+//! for `GET /jobs/{id}` to poll. Results are kept for a TTL window and then
+//! swept so the result map doesn't grow unbounded. The queue itself is
+//! bounded too: once it's full, `enqueue` rejects new work instead of
+//! growing without limit, giving callers actual backpressure rather than
+//! just a non-blocking HTTP response.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, watch, Mutex};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::classifier::{ClassifyParams, HierarchicalClassifier};
+use crate::generated::ClassificationRequest;
+use crate::service::ApiClassificationResponse;
+
+const DEFAULT_WORKERS: usize = 4;
+const DEFAULT_QUEUE_CAPACITY: usize = 256;
+const RESULT_TTL: Duration = Duration::from_secs(600);
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum JobStatus {
+    Queued,
+    Running,
+    Success,
+    Error,
+}
+
+impl JobStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            JobStatus::Queued => "queued",
+            JobStatus::Running => "running",
+            JobStatus::Success => "success",
+            JobStatus::Error => "error",
+        }
+    }
+}
+
+struct JobRecord {
+    status: JobStatus,
+    result: Option<serde_json::Value>,
+    stored_at: Instant,
+}
+
+struct Job {
+    request_id: String,
+    request: ClassificationRequest,
+}
+
+/// Why [`BackgroundRunner::enqueue`] rejected a submission.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnqueueError {
+    /// The bounded job queue is at capacity; the caller should back off.
+    QueueFull,
+    /// The runner has shut down and is no longer accepting work.
+    Closed,
+}
+
+/// Bounded worker pool draining a classification job queue, with a
+/// TTL-expiring map of results for `GET /jobs/{id}` to poll.
+pub struct BackgroundRunner {
+    sender: mpsc::Sender<Job>,
+    jobs: Arc<Mutex<HashMap<String, JobRecord>>>,
+    stop_tx: watch::Sender<bool>,
+}
+
+impl BackgroundRunner {
+    pub fn new(classifier: Arc<HierarchicalClassifier>) -> Self {
+        Self::with_workers(classifier, DEFAULT_WORKERS, DEFAULT_QUEUE_CAPACITY)
+    }
+
+    pub fn with_workers(classifier: Arc<HierarchicalClassifier>, workers: usize, queue_capacity: usize) -> Self {
+        let (sender, receiver) = mpsc::channel::<Job>(queue_capacity.max(1));
+        let receiver = Arc::new(Mutex::new(receiver));
+        let jobs: Arc<Mutex<HashMap<String, JobRecord>>> = Arc::new(Mutex::new(HashMap::new()));
+        let (stop_tx, stop_rx) = watch::channel(false);
+
+        for worker_id in 0..workers.max(1) {
+            let receiver = receiver.clone();
+            let jobs = jobs.clone();
+            let classifier = classifier.clone();
+            let mut stop_rx = stop_rx.clone();
+
+            tokio::spawn(async move {
+                info!("🧵 Background classification worker {} started", worker_id);
+
+                loop {
+                    let job = {
+                        let mut receiver = receiver.lock().await;
+                        tokio::select! {
+                            job = receiver.recv() => job,
+                            _ = stop_rx.changed() => None,
+                        }
+                    };
+
+                    let Some(job) = job else { break; };
+
+                    {
+                        let mut jobs = jobs.lock().await;
+                        if let Some(record) = jobs.get_mut(&job.request_id) {
+                            record.status = JobStatus::Running;
+                        }
+                    }
+
+                    let outcome = classify_job(&classifier, &job.request_id, &job.request).await;
+
+                    let mut jobs = jobs.lock().await;
+                    if let Some(record) = jobs.get_mut(&job.request_id) {
+                        let (status, response) = match outcome {
+                            Ok(response) => (JobStatus::Success, response),
+                            Err(e) => {
+                                warn!("Background classification {} failed: {}", job.request_id, e);
+                                (JobStatus::Error, ApiClassificationResponse {
+                                    request_id: job.request_id.clone(),
+                                    match_result: None,
+                                    alternatives: Vec::new(),
+                                    classification_steps: Vec::new(),
+                                    processing_time_ms: 0.0,
+                                    status: "error".to_string(),
+                                    error_message: Some(e.to_string()),
+                                })
+                            }
+                        };
+                        record.status = status;
+                        record.result = serde_json::to_value(&response).ok();
+                        record.stored_at = Instant::now();
+                    }
+                }
+
+                info!("🧵 Background classification worker {} stopped", worker_id);
+            });
+        }
+
+        {
+            let jobs = jobs.clone();
+            let mut stop_rx = stop_rx.clone();
+            tokio::spawn(async move {
+                let mut ticker = tokio::time::interval(SWEEP_INTERVAL);
+                loop {
+                    tokio::select! {
+                        _ = ticker.tick() => {
+                            jobs.lock().await.retain(|_, record| record.stored_at.elapsed() < RESULT_TTL);
+                        }
+                        _ = stop_rx.changed() => break,
+                    }
+                }
+            });
+        }
+
+        Self { sender, jobs, stop_tx }
+    }
+
+    /// Enqueue a classification request, returning its `request_id`
+    /// immediately. Fails with [`EnqueueError::QueueFull`] once the bounded
+    /// queue is at capacity, so a burst of submissions gets real
+    /// backpressure instead of an unbounded, ever-growing job queue.
+    pub async fn enqueue(&self, request: ClassificationRequest) -> Result<String, EnqueueError> {
+        let request_id = Uuid::new_v4().to_string();
+
+        match self.sender.try_send(Job { request_id: request_id.clone(), request }) {
+            Ok(()) => {
+                self.jobs.lock().await.insert(request_id.clone(), JobRecord {
+                    status: JobStatus::Queued,
+                    result: None,
+                    stored_at: Instant::now(),
+                });
+                Ok(request_id)
+            }
+            Err(mpsc::error::TrySendError::Full(_)) => Err(EnqueueError::QueueFull),
+            Err(mpsc::error::TrySendError::Closed(_)) => {
+                warn!("Background runner queue closed; job {} will never run", request_id);
+                Err(EnqueueError::Closed)
+            }
+        }
+    }
+
+    /// Look up a job's current state. `None` if the id is unknown or its
+    /// result has already been swept past the TTL window.
+    pub async fn get_job(&self, request_id: &str) -> Option<serde_json::Value> {
+        let jobs = self.jobs.lock().await;
+        let record = jobs.get(request_id)?;
+
+        Some(serde_json::json!({
+            "request_id": request_id,
+            "status": record.status.as_str(),
+            "result": record.result,
+        }))
+    }
+
+    /// Stop accepting new work and let in-flight jobs finish; wired into
+    /// the server's graceful shutdown.
+    pub fn shutdown(&self) {
+        let _ = self.stop_tx.send(true);
+    }
+}
+
+async fn classify_job(
+    classifier: &HierarchicalClassifier,
+    request_id: &str,
+    request: &ClassificationRequest,
+) -> anyhow::Result<ApiClassificationResponse> {
+    let start_time = std::time::Instant::now();
+
+    if request.weave_unit.text.trim().is_empty() {
+        return Ok(ApiClassificationResponse {
+            request_id: request_id.to_string(),
+            match_result: None,
+            alternatives: Vec::new(),
+            classification_steps: vec!["Error: Empty text provided".to_string()],
+            processing_time_ms: start_time.elapsed().as_millis() as f64,
+            status: "error".to_string(),
+            error_message: Some("Empty text provided".to_string()),
+        });
+    }
+
+    let params = ClassifyParams {
+        confidence_threshold: request.confidence_threshold,
+        max_alternatives: request.max_alternatives as usize,
+        semantic_ratio: request.semantic_ratio,
+        filter_by_domain: request.filter_by_domain.clone(),
+    };
+    let (primary_match, alternatives, steps) = classifier.classify(&request.weave_unit.text, &params).await?;
+
+    Ok(ApiClassificationResponse {
+        request_id: request_id.to_string(),
+        match_result: primary_match,
+        alternatives,
+        classification_steps: steps,
+        processing_time_ms: start_time.elapsed().as_millis() as f64,
+        status: "success".to_string(),
+        error_message: None,
+    })
+}
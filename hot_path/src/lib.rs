@@ -5,11 +5,17 @@
 //! - Candle ML framework for native Rust embeddings
 //! - Axum web framework for blazing fast API
 
+pub mod ann;
+pub mod batching;
 pub mod config;
 pub mod embeddings;
 pub mod classifier;
+pub mod errors;
+pub mod jobs;
+pub mod logs;
 pub mod service;
 pub mod stats;
+pub mod telemetry;
 
 pub mod generated {
     include!(concat!(env!("OUT_DIR"), "/generated.rs"));
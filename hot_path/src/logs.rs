@@ -0,0 +1,116 @@
+//! In-memory, per-level ring buffer of recent log records.
+//!
+//! [`LogBuffer`] installs as a `tracing_subscriber::Layer`, so existing
+//! `info!`/`warn!`/`error!` call sites populate it automatically with no
+//! code changes elsewhere. Each level keeps its own fixed-capacity ring
+//! (errors are rarer and worth keeping longer, so `ERROR` gets a smaller
+//! cap than `INFO`), with the oldest record evicted as a new one arrives.
+//! `GET /logs` reads from this buffer for lightweight post-hoc debugging
+//! without shipping logs to an external store.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, RwLock};
+use serde::Serialize;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+/// One structured log line captured off the `tracing` pipeline.
+#[derive(Debug, Clone, Serialize)]
+pub struct LogRecord {
+    pub timestamp: String,
+    pub level: String,
+    pub target: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+fn capacity_for(level: &Level) -> usize {
+    match *level {
+        Level::ERROR => 200,
+        Level::WARN => 500,
+        _ => 1000,
+    }
+}
+
+/// Per-level fixed-capacity ring buffers of recent log records. Cheaply
+/// `Clone`-able - clones share the same underlying buffers.
+#[derive(Clone, Default)]
+pub struct LogBuffer {
+    rings: Arc<RwLock<HashMap<&'static str, VecDeque<LogRecord>>>>,
+}
+
+impl LogBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn push(&self, level: &Level, record: LogRecord) {
+        let mut rings = self.rings.write().unwrap_or_else(|e| e.into_inner());
+        let ring = rings.entry(level.as_str()).or_default();
+        if ring.len() >= capacity_for(level) {
+            ring.pop_front();
+        }
+        ring.push_back(record);
+    }
+
+    /// Most recent records at `level` (case-insensitive; all levels when
+    /// `None`), newest first, capped at `limit`.
+    pub fn recent(&self, level: Option<&str>, limit: usize) -> Vec<LogRecord> {
+        let rings = self.rings.read().unwrap_or_else(|e| e.into_inner());
+
+        let mut records: Vec<LogRecord> = match level {
+            Some(level) => rings
+                .get(level.to_uppercase().as_str())
+                .map(|ring| ring.iter().cloned().collect())
+                .unwrap_or_default(),
+            None => rings.values().flat_map(|ring| ring.iter().cloned()).collect(),
+        };
+
+        records.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        records.truncate(limit);
+        records
+    }
+}
+
+/// Pulls the `message` field and an optional `request_id` field off a
+/// tracing event; every other field is ignored.
+#[derive(Default)]
+struct RecordVisitor {
+    message: String,
+    request_id: Option<String>,
+}
+
+impl Visit for RecordVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        match field.name() {
+            "message" => self.message = format!("{:?}", value),
+            "request_id" => self.request_id = Some(format!("{:?}", value).trim_matches('"').to_string()),
+            _ => {}
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "message" => self.message = value.to_string(),
+            "request_id" => self.request_id = Some(value.to_string()),
+            _ => {}
+        }
+    }
+}
+
+impl<S: Subscriber> Layer<S> for LogBuffer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = RecordVisitor::default();
+        event.record(&mut visitor);
+
+        self.push(event.metadata().level(), LogRecord {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            level: event.metadata().level().as_str().to_string(),
+            target: event.metadata().target().to_string(),
+            message: visitor.message,
+            request_id: visitor.request_id,
+        });
+    }
+}
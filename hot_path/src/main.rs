@@ -1,42 +1,64 @@
 use axum::{routing::{get, post}, Router};
 use tokio::net::TcpListener;
 use tracing::info;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
 use familiar_pattern_classifier::{
     config::Config,
+    logs::LogBuffer,
     service::{
         ClassificationService,
         handlers::{
             health_check,
             classify_handler,
+            classify_async_handler,
+            job_status_handler,
             status_handler,
-            reload_patterns_handler
+            reload_patterns_handler,
+            reload_config_handler,
+            logs_handler,
         },
     },
 };
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    tracing_subscriber::fmt::init();
+    let log_buffer = LogBuffer::new();
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer())
+        .with(log_buffer.clone())
+        .init();
     info!("🚀 Starting Pattern Classifier Hot Path Service");
 
-    let config = Config::from_env();
+    let config = Config::load()?;
     info!("📋 Configuration loaded");
 
-    let service = ClassificationService::new(config.clone()).await?;
-    
+    let service = ClassificationService::new(config.clone(), log_buffer).await?;
+    let shutdown_service = service.clone();
+
     let app = Router::new()
         .route("/", get(health_check))
         .route("/health", get(health_check))
         .route("/classify", post(classify_handler))
+        .route("/classify/async", post(classify_async_handler))
+        .route("/jobs/:id", get(job_status_handler))
         .route("/status", get(status_handler))
+        .route("/logs", get(logs_handler))
         .route("/reload-patterns", post(reload_patterns_handler))
+        .route("/reload-config", post(reload_config_handler))
         .with_state(service);
-        
+
     let addr = format!("0.0.0.0:{}", config.port);
     info!("🚀 Server listening on {}", addr);
-    
+
     let listener = TcpListener::bind(&addr).await?;
-    axum::serve(listener, app).await?;
-    
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async move {
+            tokio::signal::ctrl_c().await.ok();
+            info!("🛑 Shutdown signal received, draining background jobs...");
+            shutdown_service.shutdown_background();
+        })
+        .await?;
+
     Ok(())
-}
\ No newline at end of file
+}
@@ -1,12 +1,17 @@
 use uuid::Uuid;
 use crate::{
-    classifier::HierarchicalClassifier,
+    batching::EmbeddingBatcher,
+    classifier::{ClassifyParams, HierarchicalClassifier},
     config::Config,
+    errors::{ClassificationStage, ErrorReporter},
     generated::{ClassificationRequest, PatternMatch},
+    jobs::{BackgroundRunner, EnqueueError},
+    logs::{LogBuffer, LogRecord},
     stats::StatsTracker,
+    telemetry::{ClassificationEvent, TelemetrySink},
 };
 use serde::Serialize;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 use tokio::sync::Mutex;
 use tracing::{info, error};
 use axum::{extract::State, Json};
@@ -30,23 +35,47 @@ pub struct ApiClassificationResponse {
 pub struct ClassificationService {
     classifier: Arc<HierarchicalClassifier>,
     stats: Arc<Mutex<StatsTracker>>,
+    telemetry: TelemetrySink,
+    /// Live config snapshot, swapped by `/reload-config` and read through by
+    /// the classifier, batcher, telemetry sink and error reporter. A request's
+    /// `confidence_threshold`/`max_alternatives` come from the request itself;
+    /// the config's values for those two fields are not used here.
+    config: Arc<RwLock<Config>>,
+    background: Arc<BackgroundRunner>,
+    /// Coalesces concurrent `/classify` calls into batched embedding
+    /// passes; see [`crate::batching`].
+    batcher: EmbeddingBatcher,
+    /// Backs `GET /logs`; installed as a `tracing` layer in `main`.
+    log_buffer: LogBuffer,
+    /// Captures classify-path failures into `telemetry` and the log buffer.
+    error_reporter: Arc<ErrorReporter>,
 }
 
 impl ClassificationService {
-    pub async fn new(config: Config) -> Result<Self, Box<dyn std::error::Error>> {
+    pub async fn new(config: Config, log_buffer: LogBuffer) -> Result<Self, Box<dyn std::error::Error>> {
         info!("🚀 Initializing classification service...");
-        
+
+        let config = Arc::new(RwLock::new(config));
         let classifier = Arc::new(HierarchicalClassifier::new(config.clone()).await?);
         let stats = Arc::new(Mutex::new(StatsTracker::new()));
-        
+        let telemetry = TelemetrySink::new(config.clone(), stats.clone());
+        let error_reporter = Arc::new(ErrorReporter::new(telemetry.clone(), config.clone()));
+        let batcher = EmbeddingBatcher::new(classifier.clone(), error_reporter.clone(), config.clone());
+        let background = Arc::new(BackgroundRunner::new(classifier.clone()));
+
         info!("✅ Classification service initialized successfully");
-        Ok(Self { classifier, stats })
+        Ok(Self { classifier, stats, telemetry, config, background, batcher, log_buffer, error_reporter })
     }
-    
+
+    /// Most recent buffered log records, optionally filtered to one level.
+    pub fn get_logs(&self, level: Option<&str>, limit: usize) -> Vec<LogRecord> {
+        self.log_buffer.recent(level, limit)
+    }
+
     pub async fn classify_hierarchical(&self, request: &ClassificationRequest) -> Result<ApiClassificationResponse, Box<dyn std::error::Error + Send + Sync>> {
         let start_time = std::time::Instant::now();
         let request_id = Uuid::new_v4().to_string();
-        
+
         if request.weave_unit.text.trim().is_empty() {
             let processing_time = start_time.elapsed().as_millis() as f64;
             return Ok(ApiClassificationResponse {
@@ -59,17 +88,29 @@ impl ClassificationService {
                 error_message: Some("Empty text provided".to_string()),
             });
         }
-        
-        let (primary_match, alternatives, steps) = self.classifier.classify(
-            &request.weave_unit.text,
-            request.confidence_threshold.unwrap_or(0.5),
-            request.max_alternatives.unwrap_or(3) as usize,
-        ).await?;
-        
+
+        let params = ClassifyParams {
+            confidence_threshold: request.confidence_threshold,
+            max_alternatives: request.max_alternatives as usize,
+            semantic_ratio: request.semantic_ratio,
+            filter_by_domain: request.filter_by_domain.clone(),
+        };
+        let (primary_match, alternatives, steps) = self.batcher.submit(request_id.clone(), request.weave_unit.clone(), params).await?;
+
         let processing_time = start_time.elapsed().as_millis() as f64;
-        
+
         self.stats.lock().await.log_request(processing_time);
-        
+
+        let confidence_threshold = request.confidence_threshold;
+        self.telemetry.record(ClassificationEvent {
+            request_id: request_id.clone(),
+            pattern_id: primary_match.as_ref().map(|m| m.pattern_id.clone()),
+            domain: primary_match.as_ref().and_then(|m| m.get_domain()),
+            confidence: primary_match.as_ref().map(|m| m.confidence),
+            processing_time_ms: processing_time,
+            cleared_threshold: primary_match.as_ref().map_or(false, |m| m.is_confident(confidence_threshold)),
+        });
+
         Ok(ApiClassificationResponse {
             request_id,
             match_result: primary_match,
@@ -83,9 +124,22 @@ impl ClassificationService {
     
     pub async fn reload_patterns(&self) -> Result<Json<serde_json::Value>, Box<dyn std::error::Error>> {
         info!("🔄 Reloading patterns...");
-        let patterns_loaded = self.classifier.load_patterns_from_file("assets/patterns_with_embeddings.json").await?;
-        let levels_loaded = self.classifier.load_level_schemas("assets/level_schemas_with_embeddings.json").await?;
-        
+
+        let patterns_loaded = match self.classifier.load_patterns_from_file("assets/patterns_with_embeddings.json").await {
+            Ok(count) => count,
+            Err(e) => {
+                self.error_reporter.report(ClassificationStage::Reload, "reload-patterns", 0, &e.to_string());
+                return Err(e.into());
+            }
+        };
+        let levels_loaded = match self.classifier.load_level_schemas("assets/level_schemas_with_embeddings.json").await {
+            Ok(count) => count,
+            Err(e) => {
+                self.error_reporter.report(ClassificationStage::Reload, "reload-patterns", 0, &e.to_string());
+                return Err(e.into());
+            }
+        };
+
         Ok(Json(serde_json::json!({
             "status": "success",
             "patterns_loaded": patterns_loaded,
@@ -93,6 +147,48 @@ impl ClassificationService {
         })))
     }
     
+    /// Enqueue a classification request for background processing,
+    /// returning its `request_id` immediately. `Err` when the bounded job
+    /// queue is full or the runner has shut down.
+    pub async fn enqueue_classification(&self, request: ClassificationRequest) -> Result<String, EnqueueError> {
+        self.background.enqueue(request).await
+    }
+
+    /// Poll a background job's current state (queued/running/success/error).
+    pub async fn get_job(&self, request_id: &str) -> Option<serde_json::Value> {
+        self.background.get_job(request_id).await
+    }
+
+    /// Stop accepting new background jobs and let in-flight ones finish,
+    /// and drain the embedding batcher's in-flight buffer.
+    pub fn shutdown_background(&self) {
+        self.background.shutdown();
+        self.batcher.shutdown();
+    }
+
+    pub async fn reload_config(&self) -> Result<Json<serde_json::Value>, Box<dyn std::error::Error>> {
+        info!("🔄 Reloading configuration...");
+        let new_config = match Config::load() {
+            Ok(config) => config,
+            Err(e) => {
+                self.error_reporter.report(ClassificationStage::Reload, "reload-config", 0, &e.to_string());
+                return Err(e.into());
+            }
+        };
+
+        let changed = {
+            let mut current = self.config.write().map_err(|_| "config lock poisoned")?;
+            let changed = Config::diff(&current, &new_config);
+            *current = new_config;
+            changed
+        };
+
+        Ok(Json(serde_json::json!({
+            "status": "success",
+            "changed": changed
+        })))
+    }
+
     pub async fn get_status(&self) -> Result<Json<serde_json::Value>, Box<dyn std::error::Error>> {
         let stats = self.stats.lock().await;
         self.classifier.health_check().await?;
@@ -105,8 +201,10 @@ impl ClassificationService {
 
 pub mod handlers {
     use super::{ClassificationService, ApiClassificationResponse};
-    use axum::{extract::State, Json};
+    use axum::{extract::{Path, Query, State}, Json};
     use crate::generated::ClassificationRequest;
+    use crate::jobs::EnqueueError;
+    use serde::Deserialize;
     use tracing::debug;
     use uuid::Uuid;
 
@@ -144,4 +242,43 @@ pub mod handlers {
     ) -> Result<Json<serde_json::Value>, axum::http::StatusCode> {
         service.reload_patterns().await.map_err(|_| axum::http::StatusCode::INTERNAL_SERVER_ERROR)
     }
-} 
\ No newline at end of file
+
+    pub async fn reload_config_handler(
+        State(service): State<ClassificationService>,
+    ) -> Result<Json<serde_json::Value>, axum::http::StatusCode> {
+        service.reload_config().await.map_err(|_| axum::http::StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    pub async fn classify_async_handler(
+        State(service): State<ClassificationService>,
+        Json(request): Json<ClassificationRequest>,
+    ) -> Result<Json<serde_json::Value>, axum::http::StatusCode> {
+        debug!("Received async classification request: {:?}", request);
+        match service.enqueue_classification(request).await {
+            Ok(request_id) => Ok(Json(serde_json::json!({ "request_id": request_id, "status": "queued" }))),
+            Err(EnqueueError::QueueFull) => Err(axum::http::StatusCode::TOO_MANY_REQUESTS),
+            Err(EnqueueError::Closed) => Err(axum::http::StatusCode::SERVICE_UNAVAILABLE),
+        }
+    }
+
+    pub async fn job_status_handler(
+        State(service): State<ClassificationService>,
+        Path(request_id): Path<String>,
+    ) -> Result<Json<serde_json::Value>, axum::http::StatusCode> {
+        service.get_job(&request_id).await.map(Json).ok_or(axum::http::StatusCode::NOT_FOUND)
+    }
+
+    #[derive(Deserialize)]
+    pub struct LogsQuery {
+        level: Option<String>,
+        limit: Option<usize>,
+    }
+
+    pub async fn logs_handler(
+        State(service): State<ClassificationService>,
+        Query(params): Query<LogsQuery>,
+    ) -> Json<serde_json::Value> {
+        let records = service.get_logs(params.level.as_deref(), params.limit.unwrap_or(100));
+        Json(serde_json::json!({ "count": records.len(), "records": records }))
+    }
+}
\ No newline at end of file
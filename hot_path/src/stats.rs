@@ -5,6 +5,7 @@ pub struct StatsTracker {
     requests_processed: u64,
     total_processing_time: Duration,
     errors: u64,
+    telemetry_errors: u64,
 }
 
 impl StatsTracker {
@@ -13,6 +14,7 @@ impl StatsTracker {
             requests_processed: 0,
             total_processing_time: Duration::new(0, 0),
             errors: 0,
+            telemetry_errors: 0,
         }
     }
 
@@ -25,6 +27,12 @@ impl StatsTracker {
         self.errors += 1;
     }
 
+    /// Record that a telemetry sink flush failed or was rejected, so the
+    /// classify path's own errors and export errors stay distinguishable.
+    pub fn log_telemetry_error(&mut self) {
+        self.telemetry_errors += 1;
+    }
+
     pub fn get_summary(&self) -> serde_json::Value {
         let avg_time = if self.requests_processed > 0 {
             self.total_processing_time.as_millis() as f64 / self.requests_processed as f64
@@ -37,7 +45,8 @@ impl StatsTracker {
             "total_processing_time_ms": self.total_processing_time.as_millis(),
             "average_processing_time_ms": avg_time,
             "errors": self.errors,
-            "error_rate": if self.requests_processed > 0 { self.errors as f64 / self.requests_processed as f64 } else { 0.0 }
+            "error_rate": if self.requests_processed > 0 { self.errors as f64 / self.requests_processed as f64 } else { 0.0 },
+            "telemetry_errors": self.telemetry_errors
         })
     }
 } 
\ No newline at end of file
@@ -0,0 +1,172 @@
+//! Columnar telemetry sink for per-classification events.
+//!
+//! Each `/classify` call can emit one [`ClassificationEvent`]. Events are
+//! batched in-process and flushed off the hot path to an external columnar
+//! store (e.g. ClickHouse's HTTP insert endpoint), so `/classify`
+//! never blocks on export. A failed flush drops the batch and bumps
+//! `StatsTracker`'s `telemetry_errors` counter rather than retrying
+//! indefinitely - this is best-effort analytics, not a durable log.
+
+use serde::Serialize;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::sync::{mpsc, Mutex};
+use tracing::{debug, warn};
+
+use crate::config::Config;
+use crate::stats::StatsTracker;
+
+const DEFAULT_FLUSH_ROWS: usize = 500;
+const DEFAULT_FLUSH_INTERVAL_MS: u64 = 5_000;
+
+/// One structured row describing a single classification outcome.
+#[derive(Debug, Clone, Serialize)]
+pub struct ClassificationEvent {
+    pub request_id: String,
+    pub pattern_id: Option<String>,
+    pub domain: Option<String>,
+    pub confidence: Option<f64>,
+    pub processing_time_ms: f64,
+    pub cleared_threshold: bool,
+}
+
+/// One structured failure captured off the classify path - see
+/// [`crate::errors`] for how these are built and deduplicated.
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorEvent {
+    pub request_id: String,
+    pub stage: String,
+    pub text_len: usize,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backtrace: Option<String>,
+    pub occurrences: u64,
+}
+
+/// Handle for enqueuing events; the batching/flush loop runs on a
+/// background task so callers never wait on the sink.
+#[derive(Clone)]
+pub struct TelemetrySink {
+    sender: mpsc::UnboundedSender<ClassificationEvent>,
+    error_sender: mpsc::UnboundedSender<ErrorEvent>,
+}
+
+impl TelemetrySink {
+    /// Spawn the background batching task. `config.telemetry_endpoint` is
+    /// read fresh on each flush (rather than captured once) so
+    /// `/reload-config` can point the sink at a different columnar store's
+    /// HTTP insert URL - e.g. a ClickHouse `INSERT ... FORMAT JSONEachRow`
+    /// endpoint - or disable it, without a restart. `None` means flushes
+    /// are no-ops so the service still runs without an analytics backend.
+    pub fn new(config: Arc<RwLock<Config>>, stats: Arc<Mutex<StatsTracker>>) -> Self {
+        let (sender, mut receiver) = mpsc::unbounded_channel::<ClassificationEvent>();
+        let (error_sender, mut error_receiver) = mpsc::unbounded_channel::<ErrorEvent>();
+
+        tokio::spawn(async move {
+            let client = reqwest::Client::new();
+            let mut batch: Vec<ClassificationEvent> = Vec::with_capacity(DEFAULT_FLUSH_ROWS);
+            let mut error_batch: Vec<ErrorEvent> = Vec::with_capacity(DEFAULT_FLUSH_ROWS);
+            let mut ticker = tokio::time::interval(Duration::from_millis(DEFAULT_FLUSH_INTERVAL_MS));
+            let mut events_open = true;
+            let mut errors_open = true;
+
+            while events_open || errors_open {
+                let endpoint = config.read().map(|c| c.telemetry_endpoint.clone()).unwrap_or_else(|e| e.into_inner().telemetry_endpoint.clone());
+                tokio::select! {
+                    maybe_event = receiver.recv(), if events_open => {
+                        match maybe_event {
+                            Some(event) => {
+                                batch.push(event);
+                                if batch.len() >= DEFAULT_FLUSH_ROWS {
+                                    flush(&client, &endpoint, std::mem::take(&mut batch), &stats, "event").await;
+                                }
+                            }
+                            None => {
+                                flush(&client, &endpoint, std::mem::take(&mut batch), &stats, "event").await;
+                                events_open = false;
+                            }
+                        }
+                    }
+                    maybe_error = error_receiver.recv(), if errors_open => {
+                        match maybe_error {
+                            Some(event) => {
+                                error_batch.push(event);
+                                if error_batch.len() >= DEFAULT_FLUSH_ROWS {
+                                    flush(&client, &endpoint, std::mem::take(&mut error_batch), &stats, "error").await;
+                                }
+                            }
+                            None => {
+                                flush(&client, &endpoint, std::mem::take(&mut error_batch), &stats, "error").await;
+                                errors_open = false;
+                            }
+                        }
+                    }
+                    _ = ticker.tick() => {
+                        flush(&client, &endpoint, std::mem::take(&mut batch), &stats, "event").await;
+                        flush(&client, &endpoint, std::mem::take(&mut error_batch), &stats, "error").await;
+                    }
+                }
+            }
+        });
+
+        Self { sender, error_sender }
+    }
+
+    /// Enqueue an event. Never blocks: this is a best-effort send onto an
+    /// unbounded channel drained by the background flush task.
+    ///
+    /// `request_id` is cloned out *before* `send`, since `send` takes `event`
+    /// by value - reading a field off it afterward in the error branch would
+    /// be a use-after-move.
+    pub fn record(&self, event: ClassificationEvent) {
+        let request_id = event.request_id.clone();
+        if self.sender.send(event).is_err() {
+            warn!("Telemetry sink channel closed; dropping event for request {}", request_id);
+        }
+    }
+
+    /// Enqueue a captured error event. Never blocks, same as [`Self::record`]
+    /// (including the clone-before-send ordering, for the same reason).
+    pub fn record_error(&self, event: ErrorEvent) {
+        let request_id = event.request_id.clone();
+        if self.error_sender.send(event).is_err() {
+            warn!("Telemetry sink channel closed; dropping error event for request {}", request_id);
+        }
+    }
+}
+
+async fn flush<T: Serialize>(
+    client: &reqwest::Client,
+    endpoint: &Option<String>,
+    rows: Vec<T>,
+    stats: &Arc<Mutex<StatsTracker>>,
+    kind: &str,
+) {
+    if rows.is_empty() {
+        return;
+    }
+
+    let Some(endpoint) = endpoint else {
+        debug!("No telemetry endpoint configured; dropping {} buffered {} row(s)", rows.len(), kind);
+        return;
+    };
+
+    let body = rows.iter()
+        .filter_map(|row| serde_json::to_string(row).ok())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    match client.post(endpoint).header("Content-Type", "application/x-ndjson").body(body).send().await {
+        Ok(resp) if resp.status().is_success() => {
+            debug!("Flushed {} telemetry {} row(s)", rows.len(), kind);
+        }
+        Ok(resp) => {
+            warn!("Telemetry sink rejected {} batch with status {}", kind, resp.status());
+            stats.lock().await.log_telemetry_error();
+        }
+        Err(e) => {
+            warn!("Telemetry sink unreachable, dropping {} {} row(s): {}", rows.len(), kind, e);
+            stats.lock().await.log_telemetry_error();
+        }
+    }
+}